@@ -0,0 +1,97 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use graphics::types::Color;
+use serde::Deserialize;
+
+use crate::lang::Language;
+
+const CONFIG_FILE_NAME: &str = "config.json5";
+
+fn default_width() -> u8 {
+    45
+}
+
+fn default_height() -> u8 {
+    45
+}
+
+fn default_tile_size() -> u16 {
+    20
+}
+
+fn default_updates_per_move() -> u8 {
+    10
+}
+
+fn default_background() -> Color {
+    [0., 0., 0., 1.]
+}
+
+fn default_snek_color() -> Color {
+    [1., 0., 0., 1.]
+}
+
+fn default_apple_color() -> Color {
+    [0., 1., 0., 1.]
+}
+
+fn default_out_of_bounds_color() -> Color {
+    [0., 0., 1., 1.]
+}
+
+/// the game's settings, loadable from a hand-editable JSON5 file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub width: u8,
+    pub height: u8,
+    pub tile_size: u16,
+    pub updates_per_move: u8,
+    pub background: Color,
+    pub snek_color: Color,
+    pub apple_color: Color,
+    pub out_of_bounds_color: Color,
+    pub language: Language,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: default_width(),
+            height: default_height(),
+            tile_size: default_tile_size(),
+            updates_per_move: default_updates_per_move(),
+            background: default_background(),
+            snek_color: default_snek_color(),
+            apple_color: default_apple_color(),
+            out_of_bounds_color: default_out_of_bounds_color(),
+            language: Language::default(),
+        }
+    }
+}
+
+impl Config {
+    /// the config file's location if `--config` is not given: the platform's
+    /// config dir, e.g. `~/.config/snek/config.json5` on linux
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("snek").join(CONFIG_FILE_NAME))
+    }
+
+    /// loads the config from `path`, falling back to the default config if
+    /// the file doesn't exist, so a first run works without any setup
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {:?}", path))?;
+
+        json5::from_str(&content)
+            .with_context(|| format!("failed to parse config file {:?}", path))
+    }
+}
@@ -0,0 +1,45 @@
+use serde::Deserialize;
+
+/// the language the on-screen HUD text is rendered in
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    English,
+    Japanese,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Language::English
+    }
+}
+
+impl Language {
+    /// cycles to the next language, wrapping back around to the first
+    pub fn next(self) -> Self {
+        match self {
+            Language::English => Language::Japanese,
+            Language::Japanese => Language::English,
+        }
+    }
+}
+
+/// the localized HUD strings for a single language
+pub struct Strings {
+    pub game_over: &'static str,
+    pub paused: &'static str,
+}
+
+/// looks up the localized HUD strings for `lang`
+pub fn strings(lang: Language) -> Strings {
+    match lang {
+        Language::English => Strings {
+            game_over: "Game Over!",
+            paused: "Paused",
+        },
+        Language::Japanese => Strings {
+            game_over: "ゲームオーバー!",
+            paused: "一時停止",
+        },
+    }
+}
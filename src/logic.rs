@@ -1,14 +1,27 @@
-use std::{collections::LinkedList, convert::TryFrom, ops::Not};
+use std::{collections::VecDeque, convert::TryFrom, ops::Not};
 
-use graphics::{math::Matrix2d, types::Color, Transformed};
-use opengl_graphics::{GlGraphics, GlyphCache};
-use piston::{Button, ButtonArgs, ButtonState, Key, RenderArgs, UpdateArgs};
+use graphics::types::Color;
+use piston::{Button, ButtonArgs, ButtonState, ControllerAxisArgs, ControllerButton, Key, UpdateArgs};
 use rand::{prelude::ThreadRng, Rng};
 
-const BACKGROUND: Color = [0., 0., 0., 1.];
-const SNEK_COLOR: Color = [1., 0., 0., 1.];
-const OUT_OF_BOUNDS_COLOR: Color = [0., 0., 1., 1.];
-const APPLE_COLOR: Color = [0., 1., 0., 1.];
+use crate::{
+    lang::{self, Language},
+    renderer::Renderer,
+    seven_segment::SevenSegment,
+};
+
+// standard SDL-style D-pad button ids, as exposed by most gamepads
+const CONTROLLER_DPAD_UP: u8 = 11;
+const CONTROLLER_DPAD_DOWN: u8 = 12;
+const CONTROLLER_DPAD_LEFT: u8 = 13;
+const CONTROLLER_DPAD_RIGHT: u8 = 14;
+const CONTROLLER_START: u8 = 9;
+const CONTROLLER_BACK: u8 = 4;
+
+// left stick axes, as exposed by most gamepads
+const CONTROLLER_AXIS_X: u8 = 0;
+const CONTROLLER_AXIS_Y: u8 = 1;
+const CONTROLLER_AXIS_THRESHOLD: f64 = 0.5;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GameState {
@@ -50,6 +63,32 @@ impl TryFrom<&Key> for Direction {
     }
 }
 
+impl TryFrom<&ControllerButton> for Direction {
+    type Error = ();
+
+    fn try_from(value: &ControllerButton) -> Result<Self, Self::Error> {
+        match value.button {
+            CONTROLLER_DPAD_UP => Ok(Direction::Up),
+            CONTROLLER_DPAD_DOWN => Ok(Direction::Down),
+            CONTROLLER_DPAD_LEFT => Ok(Direction::Left),
+            CONTROLLER_DPAD_RIGHT => Ok(Direction::Right),
+            _ => Err(()),
+        }
+    }
+}
+
+/// converts a past-threshold analog stick axis value into the direction it
+/// points towards, returning `None` if the stick is centered
+fn axis_to_direction(axis: u8, position: f64) -> Option<Direction> {
+    match axis {
+        CONTROLLER_AXIS_X if position >= CONTROLLER_AXIS_THRESHOLD => Some(Direction::Right),
+        CONTROLLER_AXIS_X if position <= -CONTROLLER_AXIS_THRESHOLD => Some(Direction::Left),
+        CONTROLLER_AXIS_Y if position >= CONTROLLER_AXIS_THRESHOLD => Some(Direction::Down),
+        CONTROLLER_AXIS_Y if position <= -CONTROLLER_AXIS_THRESHOLD => Some(Direction::Up),
+        _ => None,
+    }
+}
+
 impl Not for &Direction {
     type Output = Direction;
 
@@ -63,125 +102,104 @@ impl Not for &Direction {
     }
 }
 
-pub struct GameSettings<'a> {
-    pub gl: GlGraphics,
+/// the set of colors used to render the board, passed in instead of being
+/// baked into the renderer (`main` takes all but `score` from the config file)
+#[derive(Copy, Clone, Debug)]
+pub struct Colors {
+    pub background: Color,
+    pub snek: Color,
+    pub apple: Color,
+    pub out_of_bounds: Color,
+    pub score: Color,
+}
+
+pub struct GameSettings {
     pub game_size: (u8, u8),
-    pub glyphs: GlyphCache<'a>,
     pub updates_per_move: u8,
     pub tile_size: u16,
+    pub colors: Colors,
+    pub language: Language,
+    pub score_digit_size: (f64, f64),
+    pub score_segment_thickness: f64,
 }
 
-fn draw_tile(
-    gl: &mut GlGraphics,
-    transform: Matrix2d,
-    tile_size: u16,
-    pos: (u8, u8),
-    color: Color,
-) {
-    graphics::rectangle(
-        color,
-        graphics::rectangle::square(
-            (pos.0 as u16 * tile_size) as f64,
-            (pos.1 as u16 * tile_size) as f64,
-            tile_size as f64,
-        ),
-        transform,
-        gl,
-    );
-}
-
-pub struct Game<'a> {
-    pub gl: GlGraphics,
+pub struct Game {
     snek: Snek,
     pub apple_pos: Option<(u8, u8)>,
     pub apple_rand: ThreadRng,
     pub game_size: (u8, u8),
     pub state: GameState,
-    pub glyphs: GlyphCache<'a>,
     pub updates_per_move: u8,
     pub tile_size: u16,
+    pub colors: Colors,
+    pub language: Language,
+    score_display: SevenSegment,
+    // the direction currently held on each analog stick axis (x, y), so a
+    // centered stick can be told apart from one still pointing the same way
+    axis_dirs: [Option<Direction>; 2],
 }
 
-impl<'a> Game<'a> {
-    pub fn new(sets: GameSettings<'a>) -> Self {
+impl Game {
+    pub fn new(sets: GameSettings) -> Self {
         Self {
             updates_per_move: sets.updates_per_move,
             tile_size: sets.tile_size,
-            gl: sets.gl,
             game_size: sets.game_size,
-            glyphs: sets.glyphs,
             state: GameState::Paused,
             apple_rand: rand::thread_rng(),
-            snek: Snek::new(sets.updates_per_move),
+            snek: Snek::new(sets.updates_per_move, sets.game_size),
             apple_pos: Default::default(),
+            colors: sets.colors,
+            language: sets.language,
+            score_display: SevenSegment::new(
+                sets.score_digit_size,
+                sets.score_segment_thickness,
+                sets.colors.score,
+            ),
+            axis_dirs: [None, None],
         }
     }
 
-    pub fn render(&mut self, args: &RenderArgs) {
+    pub fn render(&mut self, r: &mut impl Renderer) {
         let size = self.game_size;
-        let apple = self.apple_pos;
-        let snek = &mut self.snek;
-        let glyphs = &mut self.glyphs;
-        let state = self.state;
         let tile_size = self.tile_size;
+        let colors = self.colors;
+        let strings = lang::strings(self.language);
+
+        r.clear(colors.out_of_bounds);
+
+        // background
+        r.fill_rect(
+            [
+                0.,
+                0.,
+                ((size.0 as u16) * tile_size) as f64,
+                ((size.1 as u16) * tile_size) as f64,
+            ],
+            colors.background,
+        );
+
+        // apple
+        if let Some(a) = self.apple_pos {
+            let s = tile_size as f64;
+            r.fill_rect([a.0 as f64 * s, a.1 as f64 * s, s, s], colors.apple);
+        }
 
-        self.gl.draw(args.viewport(), |c, g| {
-            // clear
-            graphics::clear(OUT_OF_BOUNDS_COLOR, g);
-            // background
-            graphics::rectangle(
-                BACKGROUND,
-                [
-                    0.,
-                    0.,
-                    ((size.0 as u16) * tile_size) as f64,
-                    ((size.1 as u16) * tile_size) as f64,
-                ],
-                c.transform,
-                g,
-            );
-            // apple
-            if let Some(a) = apple {
-                draw_tile(g, c.transform, tile_size, a, APPLE_COLOR);
-            }
+        // snek
+        self.snek.queue(r, tile_size, colors.snek);
 
-            // snek
-            snek.render(g, &args, tile_size);
-
-            // score
-            graphics::text(
-                [1., 1., 1., 1.],
-                32,
-                format!("Score: {}", snek.segs.len()).as_str(),
-                glyphs,
-                c.transform.trans(10.0, 50.0),
-                g,
-            )
-            .unwrap();
-
-            // game over
-            match state {
-                GameState::Lost => graphics::text(
-                    [1., 1., 1., 1.],
-                    32,
-                    "Game Over!",
-                    glyphs,
-                    c.transform.trans(10.0, 100.0),
-                    g,
-                )
-                .unwrap(),
-                GameState::Paused => graphics::text(
-                    [1., 1., 1., 1.],
-                    32,
-                    "Paused",
-                    glyphs,
-                    c.transform.trans(10.0, 100.0),
-                    g,
-                )
-                .unwrap(),
-                _ => {},
-            }
-        });
+        // score, as a seven-segment LED-style counter
+        self.score_display
+            .push(r, [10., 10.], self.snek.segs.len() as u32);
+
+        r.flush();
+
+        // game over
+        match self.state {
+            GameState::Lost => r.draw_text((10.0, 100.0), 32, strings.game_over),
+            GameState::Paused => r.draw_text((10.0, 100.0), 32, strings.paused),
+            _ => {},
+        }
     }
 
     pub fn update(&mut self, _args: &UpdateArgs) {
@@ -190,8 +208,14 @@ impl<'a> Game<'a> {
                 self.randomize_apple();
             }
 
+            // no free cell for an apple means the board is completely full
+            let apple_pos = match self.apple_pos {
+                Some(p) => p,
+                None => return,
+            };
+
             // if snek.update returns true, we gotta randomize the apple again
-            let snek_update = self.snek.update(self.apple_pos.unwrap(), self.game_size);
+            let snek_update = self.snek.update(apple_pos, self.game_size);
             match snek_update {
                 (_, true) => self.state = GameState::Lost,
                 (true, _) => self.randomize_apple(),
@@ -201,38 +225,89 @@ impl<'a> Game<'a> {
     }
 
     pub fn keypress(&mut self, btn: &ButtonArgs) {
-        if let (ButtonState::Press, Button::Keyboard(k)) = (btn.state, btn.button) {
-            if let Ok(d) = Direction::try_from(&k) {
+        if btn.state != ButtonState::Press {
+            return;
+        }
+
+        match btn.button {
+            Button::Keyboard(k) => {
+                if let Ok(d) = Direction::try_from(&k) {
+                    // this check is to prevent the snek from turning around
+                    if !&d != self.snek.dir {
+                        self.snek.next_dir = d;
+                    }
+                }
+
+                match k {
+                    Key::Space => match self.state {
+                        GameState::Running => self.state = GameState::Paused,
+                        GameState::Paused => self.state = GameState::Running,
+                        _ => {},
+                    },
+                    Key::R => {
+                        self.state = GameState::Paused;
+                        self.snek = Snek::new(self.updates_per_move, self.game_size);
+                        self.randomize_apple();
+                    },
+                    Key::L => self.language = self.language.next(),
+                    _ => {},
+                }
+            },
+            Button::Controller(c) => {
+                if let Ok(d) = Direction::try_from(&c) {
+                    // this check is to prevent the snek from turning around
+                    if !&d != self.snek.dir {
+                        self.snek.next_dir = d;
+                    }
+                }
+
+                match c.button {
+                    CONTROLLER_START => match self.state {
+                        GameState::Running => self.state = GameState::Paused,
+                        GameState::Paused => self.state = GameState::Running,
+                        _ => {},
+                    },
+                    CONTROLLER_BACK => {
+                        self.state = GameState::Paused;
+                        self.snek = Snek::new(self.updates_per_move, self.game_size);
+                        self.randomize_apple();
+                    },
+                    _ => {},
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// handles analog stick movement, steering the snek once an axis passes
+    /// `CONTROLLER_AXIS_THRESHOLD` and clearing the tracked direction once
+    /// the stick returns to its centered position
+    pub fn controller_axis(&mut self, args: &ControllerAxisArgs) {
+        let idx = match args.axis {
+            CONTROLLER_AXIS_X => 0,
+            CONTROLLER_AXIS_Y => 1,
+            _ => return,
+        };
+
+        let dir = axis_to_direction(args.axis, args.position);
+        let prev = self.axis_dirs[idx];
+        self.axis_dirs[idx] = dir;
+
+        if let Some(d) = dir {
+            // only steer on the rising edge, so a stick held past the
+            // threshold doesn't keep reapplying the same direction on
+            // every axis event
+            if prev != Some(d) {
                 // this check is to prevent the snek from turning around
                 if !&d != self.snek.dir {
                     self.snek.next_dir = d;
                 }
             }
-
-            match k {
-                Key::Space => match self.state {
-                    GameState::Running => self.state = GameState::Paused,
-                    GameState::Paused => self.state = GameState::Running,
-                    _ => {},
-                },
-                Key::R => {
-                    self.state = GameState::Paused;
-                    self.snek = Snek::new(self.updates_per_move);
-                    self.randomize_apple();
-                },
-                _ => {},
-            }
         }
     }
 
     fn randomize_apple(&mut self) {
-        self.apple_pos = None;
-        while self.apple_pos.is_none() || self.snek.check_collides(self.apple_pos.unwrap()) {
-            self.apple_pos = Some((
-                self.apple_rand.gen_range(0, self.game_size.0),
-                self.apple_rand.gen_range(0, self.game_size.1),
-            ));
-        }
+        self.apple_pos = self.snek.random_free_cell(&mut self.apple_rand);
     }
 }
 
@@ -261,7 +336,11 @@ impl From<(u8, u8)> for SnekSeg {
 
 #[derive(Debug)]
 struct Snek {
-    pub segs: LinkedList<SnekSeg>,
+    pub segs: VecDeque<SnekSeg>,
+    // tracks which board cells are occupied by the snek's body, so
+    // self-collision and apple placement don't need to scan `segs`
+    occupied: Vec<bool>,
+    board_size: (u8, u8),
     pub dir: Direction,
     pub next_dir: Direction,
     pub move_counter: u8,
@@ -269,23 +348,32 @@ struct Snek {
 }
 
 impl Snek {
-    pub fn new(updates_per_move: u8) -> Self {
+    pub fn new(updates_per_move: u8, board_size: (u8, u8)) -> Self {
+        let mut occupied = vec![false; board_size.0 as usize * board_size.1 as usize];
+        occupied[0] = true;
+
         Self {
             updates_per_move,
-            segs: linked_list![SnekSeg(0, 0)],
+            segs: VecDeque::from(vec![SnekSeg(0, 0)]),
+            occupied,
+            board_size,
             dir: Direction::Right,
             next_dir: Direction::Right,
             move_counter: 0,
         }
     }
-    pub fn render(&mut self, gl: &mut GlGraphics, args: &RenderArgs, tile_size: u16) {
-        let iter = self.segs.iter();
 
-        gl.draw(args.viewport(), |c, gl| {
-            for s in iter {
-                draw_tile(gl, c.transform, tile_size, s.into(), SNEK_COLOR);
-            }
-        });
+    fn cell_index(&self, pos: (u8, u8)) -> usize {
+        pos.1 as usize * self.board_size.0 as usize + pos.0 as usize
+    }
+
+    /// draws every segment of the snek through `r`
+    pub fn queue(&self, r: &mut impl Renderer, tile_size: u16, color: Color) {
+        let size = tile_size as f64;
+        for s in self.segs.iter() {
+            let (x, y): (u8, u8) = s.into();
+            r.fill_rect([x as f64 * size, y as f64 * size, size, size], color);
+        }
     }
 
     /// returns (apple_eaten, game_lost)
@@ -322,29 +410,84 @@ impl Snek {
                 moved.1 = 0;
             }
 
-            // check if snek collides with self
-            lost = self.segs.iter().any(|s| s == &moved);
+            // check if snek collides with self: a single grid lookup
+            let moved_idx = self.cell_index((moved.0, moved.1));
+            lost = self.occupied[moved_idx];
 
             self.segs.push_front(moved);
+            self.occupied[moved_idx] = true;
 
             ate_apple = self.segs.front().expect("snek has no body") == &SnekSeg::from(apple_pos);
             if !ate_apple {
-                self.segs.pop_back();
+                if let Some(tail) = self.segs.pop_back() {
+                    let tail_idx = self.cell_index((tail.0, tail.1));
+                    self.occupied[tail_idx] = false;
+                }
             }
         }
 
         (ate_apple, lost)
     }
 
-    /// returns true if any part of the snek intersects with the apple
-    pub fn check_collides(&self, pos: (u8, u8)) -> bool {
-        self.segs.iter().any(|s| s == &SnekSeg::from(pos))
+    /// uniformly picks a free cell from the occupancy grid, or `None` if the
+    /// board is completely filled by the snek's body
+    pub fn random_free_cell(&self, rng: &mut ThreadRng) -> Option<(u8, u8)> {
+        let free: Vec<usize> = self
+            .occupied
+            .iter()
+            .enumerate()
+            .filter(|(_, &occupied)| !occupied)
+            .map(|(i, _)| i)
+            .collect();
+
+        if free.is_empty() {
+            return None;
+        }
+
+        let idx = free[rng.gen_range(0, free.len())];
+        Some((
+            (idx % self.board_size.0 as usize) as u8,
+            (idx / self.board_size.0 as usize) as u8,
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::renderer::{DrawCommand, NullRenderer};
+
+    #[test]
+    fn lost_game_renders_game_over_and_apple() {
+        let mut game = Game::new(GameSettings {
+            game_size: (10, 10),
+            updates_per_move: 1,
+            tile_size: 20,
+            colors: Colors {
+                background: [0., 0., 0., 1.],
+                snek: [1., 0., 0., 1.],
+                apple: [0., 1., 0., 1.],
+                out_of_bounds: [0., 0., 1., 1.],
+                score: [1., 1., 1., 1.],
+            },
+            language: Language::English,
+            score_digit_size: (16., 32.),
+            score_segment_thickness: 4.,
+        });
+        game.state = GameState::Lost;
+        game.apple_pos = Some((2, 3));
+
+        let mut r = NullRenderer::new();
+        game.render(&mut r);
+
+        assert!(r
+            .commands
+            .contains(&DrawCommand::FillRect([40., 60., 20., 20.], [0., 1., 0., 1.])));
+        assert!(r
+            .commands
+            .contains(&DrawCommand::Text("Game Over!".to_string())));
+    }
+
     #[test]
     fn direction_add() {
         let (mut x, mut y) = (0, 0);
@@ -358,4 +501,22 @@ mod tests {
         Direction::Up.move_pos(-5, &mut x, &mut y);
         assert_eq!((0, 5), (x, y));
     }
+
+    #[test]
+    fn direction_from_dpad_button() {
+        let up = ControllerButton {
+            id: 0,
+            button: CONTROLLER_DPAD_UP,
+        };
+        assert_eq!(Ok(Direction::Up), Direction::try_from(&up));
+    }
+
+    #[test]
+    fn direction_from_axis() {
+        assert_eq!(
+            Some(Direction::Right),
+            axis_to_direction(CONTROLLER_AXIS_X, 1.0)
+        );
+        assert_eq!(None, axis_to_direction(CONTROLLER_AXIS_X, 0.0));
+    }
 }
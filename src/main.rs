@@ -1,19 +1,40 @@
 #[macro_use]
 extern crate snek;
+use std::path::PathBuf;
+
 use crate::logic::Game;
 use anyhow::{Context, Result};
 use clap::{App, Arg};
+use config::Config;
 use font_kit::{family_name::FamilyName, properties::Properties, source::SystemSource};
 use glutin_window::GlutinWindow;
-use logic::GameSettings;
+use lang::Language;
+use logic::{Colors, GameSettings};
 use opengl_graphics::{GlGraphics, GlyphCache, OpenGL, TextureSettings};
-use piston::{ButtonEvent, EventSettings, Events, RenderEvent, UpdateEvent, WindowSettings};
+use piston::{
+    ButtonEvent,
+    ControllerAxisEvent,
+    EventSettings,
+    Events,
+    RenderEvent,
+    UpdateEvent,
+    WindowSettings,
+};
+use renderer::GlRenderer;
 use snek::load_font_bytes;
 
+pub mod config;
+pub mod lang;
 pub mod logic;
+pub mod renderer;
+pub mod seven_segment;
 
 const ARG_FAIL_MESSAGE: &str = "arg fail";
 
+const SCORE_COLOR: [f32; 4] = [1., 1., 1., 1.];
+const SCORE_DIGIT_SIZE: (f64, f64) = (16., 32.);
+const SCORE_SEGMENT_THICKNESS: f64 = 4.;
+
 fn main() -> Result<()> {
     let matches = App::new("Snek")
         .author("LordMZTE")
@@ -22,48 +43,76 @@ fn main() -> Result<()> {
             Arg::with_name("width")
                 .short("x")
                 .help("the width of the board")
-                .takes_value(true)
-                .default_value("45"),
+                .takes_value(true),
         )
         .arg(
             Arg::with_name("height")
                 .short("y")
                 .help("the height of the board")
-                .takes_value(true)
-                .default_value("45"),
+                .takes_value(true),
         )
         .arg(
             Arg::with_name("tile_size")
                 .short("t")
                 .help("the size that each tile will have")
-                .takes_value(true)
-                .default_value("20"),
+                .takes_value(true),
         )
         .arg(
             Arg::with_name("updates_per_move")
                 .short("u")
                 .help("how many updates it takes for the snek to move 1 tile")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("config")
+                .short("c")
+                .long("config")
+                .help("path to a JSON5 config file (defaults to the platform config dir)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("language")
+                .short("l")
+                .long("language")
+                .help("the language of the on-screen text")
                 .takes_value(true)
-                .default_value("10"),
+                .possible_values(&["english", "japanese"]),
         )
         .get_matches();
 
+    let config_path = matches
+        .value_of("config")
+        .map(PathBuf::from)
+        .or_else(Config::default_path);
+
+    let mut config = match &config_path {
+        Some(p) => Config::load(p)?,
+        None => Config::default(),
+    };
+
+    // CLI flags, when given, override whatever the config file says
+    if let Some(v) = matches.value_of("width") {
+        config.width = v.parse().context(ARG_FAIL_MESSAGE)?;
+    }
+    if let Some(v) = matches.value_of("height") {
+        config.height = v.parse().context(ARG_FAIL_MESSAGE)?;
+    }
+    if let Some(v) = matches.value_of("tile_size") {
+        config.tile_size = v.parse().context(ARG_FAIL_MESSAGE)?;
+    }
+    if let Some(v) = matches.value_of("updates_per_move") {
+        config.updates_per_move = v.parse().context(ARG_FAIL_MESSAGE)?;
+    }
+    if let Some(v) = matches.value_of("language") {
+        config.language = match v {
+            "japanese" => Language::Japanese,
+            _ => Language::English,
+        };
+    }
+
     let gl = OpenGL::V3_2;
-    let size: (u8, u8) = (
-        matches
-            .value_of("width")
-            .context(ARG_FAIL_MESSAGE)?
-            .parse()?,
-        matches
-            .value_of("height")
-            .context(ARG_FAIL_MESSAGE)?
-            .parse()?,
-    );
-
-    let tile_size: u16 = matches
-        .value_of("tile_size")
-        .context(ARG_FAIL_MESSAGE)?
-        .parse()?;
+    let size: (u8, u8) = (config.width, config.height);
+    let tile_size: u16 = config.tile_size;
 
     let mut win: GlutinWindow = WindowSettings::new(
         "Snek",
@@ -78,11 +127,22 @@ fn main() -> Result<()> {
     .build()
     .unwrap();
 
+    // Japanese needs a CJK-capable family, since the default sans-serif
+    // face is unlikely to carry those glyphs
+    let font_families = match config.language {
+        Language::Japanese => vec![
+            FamilyName::Title("Noto Sans CJK JP".into()),
+            FamilyName::Title("Yu Gothic".into()),
+            FamilyName::SansSerif,
+        ],
+        Language::English => vec![FamilyName::SansSerif],
+    };
+
     let font_bytes = load_font_bytes(
-        SystemSource::new().select_best_match(&[FamilyName::SansSerif], &Properties::new())?,
+        SystemSource::new().select_best_match(&font_families, &Properties::new())?,
     )?;
 
-    let glyphs = GlyphCache::from_bytes(
+    let mut glyphs = GlyphCache::from_bytes(
         // include_bytes!("../assets/FiraSans-Regular.ttf"),
         &*font_bytes,
         (),
@@ -90,22 +150,31 @@ fn main() -> Result<()> {
     )
     .unwrap();
 
+    let mut gl = GlGraphics::new(gl);
+
     let mut game = Game::new(GameSettings {
-        gl: GlGraphics::new(gl),
         game_size: size,
-        glyphs,
         tile_size,
-        // TODO add command line args
-        updates_per_move: matches
-            .value_of("updates_per_move")
-            .context(ARG_FAIL_MESSAGE)?
-            .parse()?,
+        updates_per_move: config.updates_per_move,
+        colors: Colors {
+            background: config.background,
+            snek: config.snek_color,
+            apple: config.apple_color,
+            out_of_bounds: config.out_of_bounds_color,
+            score: SCORE_COLOR,
+        },
+        language: config.language,
+        score_digit_size: SCORE_DIGIT_SIZE,
+        score_segment_thickness: SCORE_SEGMENT_THICKNESS,
     });
 
     let mut events = Events::new(EventSettings::new());
     while let Some(ev) = events.next(&mut win) {
         if let Some(a) = ev.render_args() {
-            game.render(&a);
+            gl.draw(a.viewport(), |c, g| {
+                let mut renderer = GlRenderer::new(g, &mut glyphs, c.transform);
+                game.render(&mut renderer);
+            });
         }
 
         if let Some(a) = ev.update_args() {
@@ -115,6 +184,10 @@ fn main() -> Result<()> {
         if let Some(k) = ev.button_args() {
             game.keypress(&k);
         }
+
+        if let Some(a) = ev.controller_axis_args() {
+            game.controller_axis(&a);
+        }
     }
 
     Ok(())
@@ -0,0 +1,155 @@
+use graphics::{math::Matrix2d, types::Color, Graphics, Transformed};
+use opengl_graphics::{GlGraphics, GlyphCache};
+
+/// abstracts the drawing backend away from the game logic, so `Game` and
+/// `Snek` can run (and be tested) without a live OpenGL context
+pub trait Renderer {
+    fn clear(&mut self, color: Color);
+    fn fill_rect(&mut self, rect: [f64; 4], color: Color);
+    fn draw_text(&mut self, pos: (f64, f64), size: u32, text: &str);
+
+    /// submits any draw calls accumulated by `fill_rect`/`draw_text`.
+    /// backends that draw immediately can leave this as a no-op
+    fn flush(&mut self) {}
+}
+
+/// accumulates colored quads so they can be submitted to the GPU in a single
+/// draw call instead of one `graphics::rectangle` call per tile
+struct TileBatch {
+    quads: Vec<([f64; 4], Color)>,
+}
+
+impl TileBatch {
+    fn new() -> Self {
+        Self { quads: Vec::new() }
+    }
+
+    fn push(&mut self, rect: [f64; 4], color: Color) {
+        self.quads.push((rect, color));
+    }
+
+    fn flush(&mut self, gl: &mut GlGraphics, transform: Matrix2d) {
+        if self.quads.is_empty() {
+            return;
+        }
+
+        let mut vertices = Vec::with_capacity(self.quads.len() * 6);
+        let mut colors = Vec::with_capacity(self.quads.len() * 6);
+
+        for ([x0, y0, w, h], color) in self.quads.drain(..) {
+            let (x1, y1) = (x0 + w, y0 + h);
+
+            for p in &[[x0, y0], [x1, y0], [x1, y1], [x1, y1], [x0, y1], [x0, y0]] {
+                let t = graphics::math::transform_pos(transform, *p);
+                vertices.push([t[0] as f32, t[1] as f32]);
+                colors.push(color);
+            }
+        }
+
+        gl.tri_list_c(&graphics::DrawState::default(), |f| f(&vertices, &colors));
+    }
+}
+
+/// draws through `opengl_graphics`, the game's normal rendering backend.
+/// quads queued via `fill_rect` are batched and submitted in one draw call
+/// by `flush`
+pub struct GlRenderer<'a, 'b> {
+    pub gl: &'a mut GlGraphics,
+    pub glyphs: &'a mut GlyphCache<'b>,
+    pub transform: Matrix2d,
+    batch: TileBatch,
+}
+
+impl<'a, 'b> GlRenderer<'a, 'b> {
+    pub fn new(gl: &'a mut GlGraphics, glyphs: &'a mut GlyphCache<'b>, transform: Matrix2d) -> Self {
+        Self {
+            gl,
+            glyphs,
+            transform,
+            batch: TileBatch::new(),
+        }
+    }
+}
+
+impl<'a, 'b> Renderer for GlRenderer<'a, 'b> {
+    fn clear(&mut self, color: Color) {
+        graphics::clear(color, self.gl);
+    }
+
+    fn fill_rect(&mut self, rect: [f64; 4], color: Color) {
+        self.batch.push(rect, color);
+    }
+
+    fn draw_text(&mut self, pos: (f64, f64), size: u32, text: &str) {
+        graphics::text(
+            [1., 1., 1., 1.],
+            size,
+            text,
+            self.glyphs,
+            self.transform.trans(pos.0, pos.1),
+            self.gl,
+        )
+        .unwrap();
+    }
+
+    fn flush(&mut self) {
+        self.batch.flush(self.gl, self.transform);
+    }
+}
+
+/// a draw command recorded by `NullRenderer`, for asserting on in tests
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Clear(Color),
+    FillRect([f64; 4], Color),
+    Text(String),
+}
+
+/// a `Renderer` that records every draw command instead of drawing,
+/// letting game logic run and be asserted on without an OpenGL context
+#[derive(Debug, Default)]
+pub struct NullRenderer {
+    pub commands: Vec<DrawCommand>,
+}
+
+impl NullRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Renderer for NullRenderer {
+    fn clear(&mut self, color: Color) {
+        self.commands.push(DrawCommand::Clear(color));
+    }
+
+    fn fill_rect(&mut self, rect: [f64; 4], color: Color) {
+        self.commands.push(DrawCommand::FillRect(rect, color));
+    }
+
+    fn draw_text(&mut self, _pos: (f64, f64), _size: u32, text: &str) {
+        self.commands.push(DrawCommand::Text(text.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_renderer_records_commands() {
+        let mut r = NullRenderer::new();
+        r.clear([0., 0., 0., 1.]);
+        r.fill_rect([1., 2., 3., 4.], [1., 0., 0., 1.]);
+        r.draw_text((0., 0.), 32, "Paused");
+
+        assert_eq!(
+            vec![
+                DrawCommand::Clear([0., 0., 0., 1.]),
+                DrawCommand::FillRect([1., 2., 3., 4.], [1., 0., 0., 1.]),
+                DrawCommand::Text("Paused".to_string()),
+            ],
+            r.commands
+        );
+    }
+}
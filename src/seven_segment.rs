@@ -0,0 +1,100 @@
+use graphics::types::Color;
+
+use crate::renderer::Renderer;
+
+// which of the 7 segments (a..g) are lit for each decimal digit, going
+// clockwise from the top: a, b, c, d, e, f, g (g being the middle bar)
+const DIGIT_SEGMENTS: [[bool; 7]; 10] = [
+    [true, true, true, true, true, true, false],
+    [false, true, true, false, false, false, false],
+    [true, true, false, true, true, false, true],
+    [true, true, true, true, false, false, true],
+    [false, true, true, false, false, true, true],
+    [true, false, true, true, false, true, true],
+    [true, false, true, true, true, true, true],
+    [true, true, true, false, false, false, false],
+    [true, true, true, true, true, true, true],
+    [true, true, true, true, false, true, true],
+];
+
+/// draws `u32`s as classic seven-segment LED digits, out of plain quads, so
+/// the scoreboard no longer depends on a loaded TTF
+pub struct SevenSegment {
+    pub digit_size: (f64, f64),
+    pub segment_thickness: f64,
+    pub color: Color,
+}
+
+impl SevenSegment {
+    pub fn new(digit_size: (f64, f64), segment_thickness: f64, color: Color) -> Self {
+        Self {
+            digit_size,
+            segment_thickness,
+            color,
+        }
+    }
+
+    /// draws every digit of `value` through `r`, left-aligned at `pos`
+    pub fn push(&self, r: &mut impl Renderer, pos: [f64; 2], value: u32) {
+        let spacing = self.digit_size.0 + self.segment_thickness;
+
+        for (i, digit) in digits_of(value).into_iter().enumerate() {
+            let origin = [pos[0] + i as f64 * spacing, pos[1]];
+            self.push_digit(r, origin, DIGIT_SEGMENTS[digit as usize]);
+        }
+    }
+
+    fn push_digit(&self, r: &mut impl Renderer, origin: [f64; 2], segs: [bool; 7]) {
+        let (w, h) = self.digit_size;
+        let t = self.segment_thickness;
+        let [x, y] = origin;
+
+        // a: top, b: upper right, c: lower right, d: bottom,
+        // e: lower left, f: upper left, g: middle
+        let rects = [
+            [x, y, w, t],
+            [x + w - t, y, t, h / 2.],
+            [x + w - t, y + h / 2., t, h / 2.],
+            [x, y + h - t, w, t],
+            [x, y + h / 2., t, h / 2.],
+            [x, y, t, h / 2.],
+            [x, y + h / 2. - t / 2., w, t],
+        ];
+
+        for (lit, rect) in segs.iter().zip(rects.iter()) {
+            if *lit {
+                r.fill_rect(*rect, self.color);
+            }
+        }
+    }
+}
+
+/// splits `value` into its decimal digits, most significant first
+fn digits_of(mut value: u32) -> Vec<u8> {
+    if value == 0 {
+        return vec![0];
+    }
+
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push((value % 10) as u8);
+        value /= 10;
+    }
+    digits.reverse();
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digits_of_zero() {
+        assert_eq!(vec![0], digits_of(0));
+    }
+
+    #[test]
+    fn digits_of_multi_digit() {
+        assert_eq!(vec![1, 2, 3], digits_of(123));
+    }
+}